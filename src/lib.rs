@@ -8,51 +8,106 @@
 //! Generally, it's a bad idea to represent a monetary value as float.
 //! If you want to implement something similar for real-world use-cases, read
 //! [this](https://deque.blog/2017/08/17/a-study-of-4-money-class-designs-featuring-martin-fowler-kent-beck-and-ward-cunningham-implementations/) first.
-//!
-//! Only works on nightly Rust for now [until slice patterns are stabilized](https://github.com/rust-lang/rust/issues/23121).
 
-// We use a nightly feature for making our code
-// a little easier on the eye.
-// This can be removed, as soon as
-// [slice patterns are stabilized](https://github.com/rust-lang/rust/issues/23121)
-#![feature(slice_patterns)]
+use std::collections::HashMap;
+// This error will be thrown, when the integer part of our monetary value
+// cannot be parsed (e.g. if it's not a whole number).
+use std::num::ParseIntError;
+
+/// Error returned when a currency token cannot be recognised.
+///
+/// Following the way `rust-bitcoin` separates `ParseDenominationError` from
+/// `ParseAmountError`, the currency-parsing failure is its own type rather
+/// than an opaque string folded into the amount error path.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseCurrencyError {
+    /// The token that did not name a known currency.
+    token: String,
+}
 
-// Failure is a crate for making custom error types
-// easier to write and integrate with existing errors.
-#[macro_use]
-extern crate failure;
+impl std::fmt::Display for ParseCurrencyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Unknown currency: {}", self.token)
+    }
+}
 
-// This error will be thrown, when our monetary value cannot be parsed
-// (e.g if it's not a floating point number).
-use std::num::ParseFloatError;
+impl std::error::Error for ParseCurrencyError {}
 
 /// Our custom error type.
-#[derive(Debug, Fail, PartialEq)]
+///
+/// It is `#[non_exhaustive]` so that new failure modes can be added without
+/// breaking downstream `match`es, and it implements
+/// [`std::error::Error::source`] so callers can introspect the underlying
+/// cause of a parse failure.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum MoneyError {
-    /// Error while parsing the amount as float
-    #[fail(display = "Invalid input: {}", _0)]
-    ParseAmount(ParseFloatError),
-    /// Error while parsing currency
-    #[fail(display = "{}", _0)]
-    ParseCurrency(String),
-    /// General formatting error (e.g. input string does not consist of amount and currency)
-    #[fail(display = "{}", _0)]
+    /// Error while parsing the amount as an integer number of minor units.
+    ParseAmount(ParseIntError),
+    /// Error while parsing the currency.
+    ParseCurrency(ParseCurrencyError),
+    /// General formatting error (e.g. input string does not consist of amount and currency).
     ParseFormatting(String),
+    /// Arithmetic was attempted on two amounts in different currencies.
+    CurrencyMismatch { left: Currency, right: Currency },
+    /// No exchange rate was registered for the requested conversion.
+    NoRate { from: Currency, to: Currency },
 }
 
-/// A conversion from `std::num::ParseFloatError`
+impl std::fmt::Display for MoneyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MoneyError::ParseAmount(e) => write!(f, "Invalid input: {}", e),
+            MoneyError::ParseCurrency(e) => write!(f, "{}", e),
+            MoneyError::ParseFormatting(msg) => write!(f, "{}", msg),
+            MoneyError::CurrencyMismatch { left, right } => {
+                write!(f, "Currency mismatch: {:?} vs {:?}", left, right)
+            }
+            MoneyError::NoRate { from, to } => {
+                write!(f, "No exchange rate from {:?} to {:?}", from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for MoneyError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MoneyError::ParseAmount(e) => Some(e),
+            MoneyError::ParseCurrency(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+/// A conversion from `std::num::ParseIntError`
 /// into our custom MoneyError type.
-impl From<ParseFloatError> for MoneyError {
-    fn from(e: ParseFloatError) -> Self {
+impl From<ParseIntError> for MoneyError {
+    fn from(e: ParseIntError) -> Self {
         MoneyError::ParseAmount(e)
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// A conversion from the dedicated currency-parse error into the top-level
+/// `MoneyError`, so `Money::from_str` can compose the two error paths with
+/// the `?` operator.
+impl From<ParseCurrencyError> for MoneyError {
+    fn from(e: ParseCurrencyError) -> Self {
+        MoneyError::ParseCurrency(e)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 /// Our Money type.
-/// We derive `PartialEq` for comparing objects
+///
+/// Instead of a floating point number, we store the value as an integer
+/// count of *minor units* (e.g. cents), together with its currency. The
+/// currency knows how many minor units make up one major unit, so `1012`
+/// cents in `Currency::Usd` represents `$10.12`. Keeping everything in
+/// integers means `PartialEq` is exact and we never accumulate float
+/// rounding error.
 pub struct Money {
-    amount: f32,
+    minor: i64,
     currency: Currency,
 }
 
@@ -63,47 +118,476 @@ pub struct Money {
 /// let cash = "10.12 $".parse::<Money>();
 /// ```
 impl Money {
-    fn new(amount: f32, currency: Currency) -> Self {
-        Money { amount, currency }
+    fn new(minor: i64, currency: Currency) -> Self {
+        Money { minor, currency }
     }
 }
 
+/// Adding two amounts only makes sense when they share a currency, so the
+/// output is fallible: we return `MoneyError::CurrencyMismatch` when the
+/// operands disagree rather than silently producing a nonsensical result.
+impl std::ops::Add for Money {
+    type Output = Result<Money, MoneyError>;
+
+    fn add(self, rhs: Money) -> Self::Output {
+        if self.currency != rhs.currency {
+            return Err(MoneyError::CurrencyMismatch {
+                left: self.currency,
+                right: rhs.currency,
+            });
+        }
+        Ok(Money::new(self.minor + rhs.minor, self.currency))
+    }
+}
+
+/// Subtraction is fallible for the same reason as addition: the two operands
+/// must be in the same currency.
+impl std::ops::Sub for Money {
+    type Output = Result<Money, MoneyError>;
+
+    fn sub(self, rhs: Money) -> Self::Output {
+        if self.currency != rhs.currency {
+            return Err(MoneyError::CurrencyMismatch {
+                left: self.currency,
+                right: rhs.currency,
+            });
+        }
+        Ok(Money::new(self.minor - rhs.minor, self.currency))
+    }
+}
+
+/// Scaling an amount by a plain integer factor (e.g. three of the same item)
+/// is always well-defined, so unlike `Add`/`Sub` it is infallible.
+impl std::ops::Mul<i64> for Money {
+    type Output = Money;
+
+    fn mul(self, factor: i64) -> Self::Output {
+        Money::new(self.minor * factor, self.currency)
+    }
+}
+
+/// Parse a decimal amount such as `"10.12"` into an integer number of minor
+/// units for a currency with the given `exponent`.
+///
+/// The string is split on the decimal separator; the fractional part is
+/// padded with zeroes or truncated to `exponent` digits and combined with the
+/// whole part as `whole * 10^exponent + frac`.
+fn parse_minor(s: &str, exponent: u32) -> Result<i64, MoneyError> {
+    let negative = s.starts_with('-');
+    let digits = s.trim_start_matches(|c| c == '-' || c == '+');
+
+    let mut parts = digits.splitn(2, '.');
+    let whole_str = parts.next().unwrap_or("");
+    let frac_str = parts.next().unwrap_or("");
+
+    let whole: i64 = if whole_str.is_empty() {
+        0
+    } else {
+        whole_str.parse()?
+    };
+
+    // Pad or truncate the fractional part to the currency's exponent so that
+    // "10.1" and "10.10" both map to the same number of minor units. We keep
+    // the first `exponent` *characters* (not bytes) and reject anything that
+    // is not a digit, so a multi-byte char cannot slice mid-codepoint.
+    let width = exponent as usize;
+    let mut frac_digits = String::with_capacity(width);
+    for c in frac_str.chars().take(width) {
+        if !c.is_ascii_digit() {
+            return Err(MoneyError::ParseFormatting(format!(
+                "Invalid fractional digit: {:?}",
+                c
+            )));
+        }
+        frac_digits.push(c);
+    }
+    while frac_digits.len() < width {
+        frac_digits.push('0');
+    }
+    let frac: i64 = if frac_digits.is_empty() {
+        0
+    } else {
+        frac_digits.parse()?
+    };
+
+    // The exponent may be attacker-supplied via `Currency::custom`, so compute
+    // `10^exponent` and the scaled whole part with checked arithmetic and
+    // surface overflow as a parse error rather than panicking/wrapping.
+    let scale = 10i64.checked_pow(exponent).ok_or_else(|| {
+        MoneyError::ParseFormatting(format!("Currency exponent {} is too large", exponent))
+    })?;
+    let minor = whole
+        .checked_mul(scale)
+        .and_then(|w| w.checked_add(frac))
+        .ok_or_else(|| MoneyError::ParseFormatting("Amount is too large".into()))?;
+    Ok(if negative { -minor } else { minor })
+}
+
 /// We implement `std::str::FromStr` for converting
 /// a string into Money.
 impl std::str::FromStr for Money {
     type Err = MoneyError;
 
-    /// Right now, we are using a nightly feature for string to type conversion.
-    /// See [slice patterns](https://github.com/rust-lang/rust/issues/23121).
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Money::parse_with(s, &[])
+    }
+}
+
+impl Money {
+    /// Parse a string into `Money`, recognising the `extra` custom currencies
+    /// in addition to the built-in ISO set.
+    ///
+    /// This is the dynamically-typed parsing path: a currency registered at
+    /// runtime via [`Currency::custom`] can be passed here and matched by its
+    /// code or symbol, so `Money` can be parsed in currencies the enum does
+    /// not name.
+    pub fn parse_with(s: &str, extra: &[Currency]) -> Result<Self, MoneyError> {
         let parts: Vec<&str> = s.split_whitespace().collect();
 
         match parts[..] {
-            [amount, currency] => Ok(Money::new(amount.parse()?, currency.parse()?)),
+            [amount, currency] => {
+                let currency = match extra.iter().find(|c| c.matches(currency)) {
+                    Some(c) => c.clone(),
+                    None => currency.parse()?,
+                };
+                let minor = parse_minor(amount, currency.minor_units())?;
+                Ok(Money::new(minor, currency))
+            }
             _ => Err(MoneyError::ParseFormatting(
                 "Expecting amount and currency".into(),
             )),
         }
     }
+
+    /// Split this amount into `parts` pieces that sum back to exactly the
+    /// original, with no lost minor units.
+    ///
+    /// The amount is divided evenly and the leftover minor units are handed
+    /// out one per piece from the front, so `$10.00` allocated into three
+    /// pieces yields `[$3.34, $3.33, $3.33]`. No rounding strategy is needed
+    /// because the remainder is distributed exactly rather than discarded.
+    pub fn allocate(&self, parts: usize) -> Vec<Money> {
+        assert!(parts > 0, "cannot allocate into zero parts");
+
+        let parts_i = parts as i64;
+        let base = self.minor / parts_i;
+        let remainder = self.minor % parts_i;
+        let step = if self.minor < 0 { -1 } else { 1 };
+        let leftover = remainder.abs();
+
+        (0..parts_i)
+            .map(|i| {
+                let minor = if i < leftover { base + step } else { base };
+                Money::new(minor, self.currency.clone())
+            })
+            .collect()
+    }
 }
 
-/// Supported currencies
-#[derive(Debug, PartialEq)]
-enum Currency {
-    Dollar,
-    Euro,
+/// Where the currency symbol sits relative to the number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SymbolPosition {
+    /// Symbol before the number, e.g. `$1,234.56`.
+    Prefix,
+    /// Symbol after the number, e.g. `1.234,56 €`.
+    Suffix,
+}
+
+/// Describes how a `Money` value should be rendered as text, inspired by
+/// `steel-cent`'s formatter. Every `Currency` provides a sensible default via
+/// [`Currency::format_spec`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormatSpec {
+    /// The currency symbol, e.g. `"$"` or `"€"`.
+    pub symbol: String,
+    /// Whether the symbol is placed before or after the number.
+    pub symbol_position: SymbolPosition,
+    /// Character inserted every three digits of the whole part.
+    pub thousands_separator: char,
+    /// Character separating the whole and fractional parts.
+    pub decimal_separator: char,
+    /// Number of fractional digits to display.
+    pub fraction_digits: u32,
+}
+
+impl Money {
+    /// Render this amount as a string according to `spec`.
+    ///
+    /// The minor-unit value is split into whole and fractional parts, group
+    /// separators are inserted every three digits from the right, and the
+    /// symbol is placed according to `spec.symbol_position`.
+    pub fn format(&self, spec: &FormatSpec) -> String {
+        let negative = self.minor < 0;
+        let divisor = 10i64.pow(spec.fraction_digits);
+        let abs = self.minor.abs();
+        let whole = abs / divisor;
+        let frac = abs % divisor;
+
+        // Group the whole part every three digits from the right.
+        let whole_digits = whole.to_string();
+        let mut grouped = String::new();
+        for (i, c) in whole_digits.chars().enumerate() {
+            if i > 0 && (whole_digits.len() - i) % 3 == 0 {
+                grouped.push(spec.thousands_separator);
+            }
+            grouped.push(c);
+        }
+
+        let mut number = grouped;
+        if spec.fraction_digits > 0 {
+            number.push(spec.decimal_separator);
+            number.push_str(&format!(
+                "{:0width$}",
+                frac,
+                width = spec.fraction_digits as usize
+            ));
+        }
+
+        let body = match spec.symbol_position {
+            SymbolPosition::Prefix => format!("{}{}", spec.symbol, number),
+            SymbolPosition::Suffix => format!("{} {}", number, spec.symbol),
+        };
+
+        if negative {
+            format!("-{}", body)
+        } else {
+            body
+        }
+    }
+}
+
+/// The default `Display` uses the currency's default [`FormatSpec`].
+impl std::fmt::Display for Money {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.format(&self.currency.format_spec()))
+    }
+}
+
+/// The code, symbol and minor-unit exponent of a currency registered at
+/// runtime, as `doubloon` allows. This lets callers work with currencies
+/// beyond the built-in ISO set without adding a new enum variant.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CurrencyInfo {
+    /// Three-letter (or arbitrary) currency code, e.g. `"BTC"`.
+    pub code: String,
+    /// Display symbol, e.g. `"₿"`.
+    pub symbol: String,
+    /// Number of minor-unit digits.
+    pub exponent: u32,
+}
+
+/// Supported currencies.
+///
+/// The named variants cover common ISO 4217 codes; the `Custom` variant
+/// carries a [`CurrencyInfo`] so a currency can be registered at runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Currency {
+    /// US dollar (USD).
+    Usd,
+    /// Euro (EUR).
+    Eur,
+    /// Pound sterling (GBP).
+    Gbp,
+    /// Japanese yen (JPY) — no minor unit.
+    Jpy,
+    /// Swiss franc (CHF).
+    Chf,
+    /// A currency registered at runtime.
+    Custom(CurrencyInfo),
+}
+
+impl Currency {
+    /// Build a dynamically-typed currency from its code, symbol and
+    /// minor-unit exponent.
+    pub fn custom(code: impl Into<String>, symbol: impl Into<String>, exponent: u32) -> Self {
+        Currency::Custom(CurrencyInfo {
+            code: code.into(),
+            symbol: symbol.into(),
+            exponent,
+        })
+    }
+
+    /// The three-letter ISO 4217 code (or the custom code).
+    pub fn code(&self) -> &str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Eur => "EUR",
+            Currency::Gbp => "GBP",
+            Currency::Jpy => "JPY",
+            Currency::Chf => "CHF",
+            Currency::Custom(info) => &info.code,
+        }
+    }
+
+    /// The display symbol for this currency.
+    pub fn symbol(&self) -> &str {
+        match self {
+            Currency::Usd => "$",
+            Currency::Eur => "€",
+            Currency::Gbp => "£",
+            Currency::Jpy => "¥",
+            Currency::Chf => "CHF",
+            Currency::Custom(info) => &info.symbol,
+        }
+    }
+
+    /// Number of decimal digits in one major unit of this currency, i.e. the
+    /// exponent relating minor units (cents) to major units. Note that the
+    /// yen has no minor unit, so its exponent is `0`.
+    pub fn minor_units(&self) -> u32 {
+        match self {
+            Currency::Jpy => 0,
+            Currency::Usd | Currency::Eur | Currency::Gbp | Currency::Chf => 2,
+            Currency::Custom(info) => info.exponent,
+        }
+    }
+
+    /// Whether `token` names this currency, matching its code
+    /// case-insensitively or its symbol exactly.
+    fn matches(&self, token: &str) -> bool {
+        token.eq_ignore_ascii_case(self.code()) || token == self.symbol()
+    }
+
+    /// The default formatting rules for this currency, e.g. `$1,234.56` for
+    /// the dollar and `1.234,56 €` for the euro.
+    pub fn format_spec(&self) -> FormatSpec {
+        match self {
+            Currency::Eur => FormatSpec {
+                symbol: "€".into(),
+                symbol_position: SymbolPosition::Suffix,
+                thousands_separator: '.',
+                decimal_separator: ',',
+                fraction_digits: 2,
+            },
+            // The remaining currencies share the `$1,234.56` layout, varying
+            // only by symbol and number of fractional digits.
+            other => FormatSpec {
+                symbol: other.symbol().to_string(),
+                symbol_position: SymbolPosition::Prefix,
+                thousands_separator: ',',
+                decimal_separator: '.',
+                fraction_digits: other.minor_units(),
+            },
+        }
+    }
 }
 
 impl std::str::FromStr for Currency {
-    type Err = MoneyError;
+    type Err = ParseCurrencyError;
 
-    /// Match based on the input string and return the correct
-    /// `Currency` type.
+    /// Match based on the input string and return the correct `Currency`.
+    /// Both the alphabetic code and the symbol are accepted, codes
+    /// case-insensitively, along with a few human-friendly aliases.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_ref() {
-            "dollar" | "$" => Ok(Currency::Dollar),
-            "euro" | "eur" | "â‚¬" => Ok(Currency::Euro),
-            _ => Err(MoneyError::ParseCurrency("Unknown currency".into())),
+            "usd" | "dollar" | "$" => Ok(Currency::Usd),
+            "eur" | "euro" | "€" => Ok(Currency::Eur),
+            "gbp" | "pound" | "£" => Ok(Currency::Gbp),
+            "jpy" | "yen" | "¥" => Ok(Currency::Jpy),
+            "chf" | "franc" => Ok(Currency::Chf),
+            _ => Err(ParseCurrencyError { token: s.into() }),
+        }
+    }
+}
+
+/// Strategies for rounding a fractional minor-unit value to a whole one,
+/// as used by `rust_decimal`-based money designs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundStrategy {
+    /// Round half away from zero (`2.5 -> 3`, `-2.5 -> -3`).
+    HalfUp,
+    /// Banker's rounding: round half to the nearest even (`2.5 -> 2`, `3.5 -> 4`).
+    HalfEven,
+    /// Round towards positive infinity.
+    Ceiling,
+    /// Round towards negative infinity.
+    Floor,
+    /// Round towards zero, discarding the fractional part.
+    TowardZero,
+}
+
+impl Default for RoundStrategy {
+    fn default() -> Self {
+        RoundStrategy::HalfUp
+    }
+}
+
+impl RoundStrategy {
+    /// Round `value` to a whole number of minor units according to this strategy.
+    fn round(self, value: f64) -> i64 {
+        let rounded = match self {
+            RoundStrategy::HalfUp => value.round(),
+            RoundStrategy::Ceiling => value.ceil(),
+            RoundStrategy::Floor => value.floor(),
+            RoundStrategy::TowardZero => value.trunc(),
+            RoundStrategy::HalfEven => {
+                let floor = value.floor();
+                let diff = value - floor;
+                if diff > 0.5 {
+                    floor + 1.0
+                } else if diff < 0.5 {
+                    floor
+                } else if (floor as i64) % 2 == 0 {
+                    floor
+                } else {
+                    floor + 1.0
+                }
+            }
+        };
+        rounded as i64
+    }
+}
+
+/// A `Bank` knows the exchange rates between currencies and can convert
+/// `Money` from one into another, mirroring Kent Beck's TDD money example.
+#[derive(Debug, Default)]
+pub struct Bank {
+    rates: HashMap<(Currency, Currency), f64>,
+    rounding: RoundStrategy,
+}
+
+impl Bank {
+    /// Create a bank with no registered exchange rates and the default
+    /// ([`RoundStrategy::HalfUp`]) rounding strategy.
+    pub fn new() -> Self {
+        Bank {
+            rates: HashMap::new(),
+            rounding: RoundStrategy::default(),
+        }
+    }
+
+    /// Choose the rounding strategy applied when a conversion produces a
+    /// fractional number of minor units.
+    pub fn with_rounding(mut self, rounding: RoundStrategy) -> Self {
+        self.rounding = rounding;
+        self
+    }
+
+    /// Register the rate for converting `from` into `to`.
+    pub fn add_rate(&mut self, from: Currency, to: Currency, rate: f64) {
+        self.rates.insert((from, to), rate);
+    }
+
+    /// Convert `money` into the target currency.
+    ///
+    /// Converting a currency to itself is the identity and needs no
+    /// registered rate. Otherwise the `(from, to)` rate is looked up, the
+    /// minor-unit amount multiplied by it, and the result rounded to a whole
+    /// minor unit of the target currency using the bank's [`RoundStrategy`].
+    /// A missing rate yields `MoneyError::NoRate`.
+    pub fn convert(&self, money: &Money, to: Currency) -> Result<Money, MoneyError> {
+        if money.currency == to {
+            return Ok(money.clone());
+        }
+        match self.rates.get(&(money.currency.clone(), to.clone())) {
+            Some(rate) => {
+                let converted = self.rounding.round(money.minor as f64 * rate);
+                Ok(Money::new(converted, to))
+            }
+            None => Err(MoneyError::NoRate {
+                from: money.currency.clone(),
+                to,
+            }),
         }
     }
 }
@@ -131,22 +615,29 @@ mod tests {
             (
                 "100 Euro",
                 Money {
-                    amount: 100.0,
-                    currency: Currency::Euro,
+                    minor: 10000,
+                    currency: Currency::Eur,
                 },
             ),
             (
                 "10 $",
                 Money {
-                    amount: 10.0,
-                    currency: Currency::Dollar,
+                    minor: 1000,
+                    currency: Currency::Usd,
                 },
             ),
             (
                 "42.4 DOLLAR",
                 Money {
-                    amount: 42.4,
-                    currency: Currency::Dollar,
+                    minor: 4240,
+                    currency: Currency::Usd,
+                },
+            ),
+            (
+                "10.12 $",
+                Money {
+                    minor: 1012,
+                    currency: Currency::Usd,
                 },
             ),
         ];
@@ -155,4 +646,119 @@ mod tests {
             assert_eq!(input.parse::<Money>(), Ok(output));
         }
     }
+
+    #[test]
+    fn test_arithmetic() {
+        let five = Money::new(500, Currency::Usd);
+        let three = Money::new(300, Currency::Usd);
+
+        assert_eq!(
+            (five.clone() + three.clone()).unwrap(),
+            Money::new(800, Currency::Usd)
+        );
+        assert_eq!(
+            (five.clone() - three).unwrap(),
+            Money::new(200, Currency::Usd)
+        );
+        assert_eq!(five.clone() * 3, Money::new(1500, Currency::Usd));
+
+        let euros = Money::new(500, Currency::Eur);
+        assert_eq!(
+            five + euros,
+            Err(MoneyError::CurrencyMismatch {
+                left: Currency::Usd,
+                right: Currency::Eur,
+            })
+        );
+    }
+
+    #[test]
+    fn test_bank_conversion() {
+        let mut bank = Bank::new();
+        bank.add_rate(Currency::Usd, Currency::Eur, 0.9);
+
+        let dollars = Money::new(1000, Currency::Usd);
+        assert_eq!(
+            bank.convert(&dollars, Currency::Eur).unwrap(),
+            Money::new(900, Currency::Eur)
+        );
+
+        // Converting to the same currency is the identity.
+        assert_eq!(bank.convert(&dollars, Currency::Usd).unwrap(), dollars);
+
+        // A missing rate is reported, not guessed.
+        assert_eq!(
+            bank.convert(&Money::new(500, Currency::Eur), Currency::Usd),
+            Err(MoneyError::NoRate {
+                from: Currency::Eur,
+                to: Currency::Usd,
+            })
+        );
+    }
+
+    #[test]
+    fn test_display_formatting() {
+        assert_eq!(
+            Money::new(123456, Currency::Usd).to_string(),
+            "$1,234.56"
+        );
+        assert_eq!(
+            Money::new(123456, Currency::Eur).to_string(),
+            "1.234,56 €"
+        );
+        assert_eq!(Money::new(-500, Currency::Usd).to_string(), "-$5.00");
+    }
+
+    #[test]
+    fn test_iso_codes_and_custom_currency() {
+        // Codes and symbols are both accepted, codes case-insensitively.
+        assert_eq!("5 GBP".parse::<Money>(), Ok(Money::new(500, Currency::Gbp)));
+        assert_eq!("5 £".parse::<Money>(), Ok(Money::new(500, Currency::Gbp)));
+
+        // The yen has no minor unit, so the amount is the minor-unit value.
+        assert_eq!("500 jpy".parse::<Money>(), Ok(Money::new(500, Currency::Jpy)));
+
+        // A currency registered at runtime can be parsed via `parse_with`.
+        let btc = Currency::custom("BTC", "₿", 8);
+        assert_eq!(
+            Money::parse_with("1.5 BTC", &[btc.clone()]),
+            Ok(Money::new(150_000_000, btc))
+        );
+    }
+
+    #[test]
+    fn test_allocate_loses_no_cents() {
+        let ten = Money::new(1000, Currency::Usd);
+        let parts = ten.allocate(3);
+
+        assert_eq!(
+            parts,
+            vec![
+                Money::new(334, Currency::Usd),
+                Money::new(333, Currency::Usd),
+                Money::new(333, Currency::Usd),
+            ]
+        );
+
+        let total: i64 = parts.iter().map(|m| m.minor).sum();
+        assert_eq!(total, 1000);
+    }
+
+    #[test]
+    fn test_rounding_strategies() {
+        let mut bank = Bank::new().with_rounding(RoundStrategy::Floor);
+        // 101 minor units * 0.5 = 50.5.
+        bank.add_rate(Currency::Usd, Currency::Eur, 0.5);
+        let amount = Money::new(101, Currency::Usd);
+
+        assert_eq!(
+            bank.convert(&amount, Currency::Eur).unwrap(),
+            Money::new(50, Currency::Eur)
+        );
+
+        // Banker's rounding sends the exact half to the nearest even value.
+        assert_eq!(RoundStrategy::HalfEven.round(50.5), 50);
+        assert_eq!(RoundStrategy::HalfEven.round(51.5), 52);
+        assert_eq!(RoundStrategy::HalfUp.round(50.5), 51);
+    }
 }